@@ -0,0 +1,111 @@
+//! Control subsystem deciding whether `Colored` methods should emit escape sequences.
+//!
+//! Colorizing decisions follow the common convention shared by many CLI tools:
+//!
+//! - `CLICOLOR_FORCE` set to anything other than `0` forces color on, overriding everything else.
+//! - Otherwise, if `NO_COLOR` is set (to any value), color is disabled.
+//! - Otherwise, if `CLICOLOR` is set to `0`, color is disabled.
+//! - Otherwise color is emitted only when stdout is a terminal (or always, if the `atty` feature
+//!   is not enabled, since the tty check then can't be performed).
+//!
+//! [`set_override`] and [`unset_override`] let applications and tests force a decision regardless
+//! of the environment.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+const OVERRIDE_UNSET: u8 = 0;
+const OVERRIDE_TRUE: u8 = 1;
+const OVERRIDE_FALSE: u8 = 2;
+
+static OVERRIDE: AtomicU8 = AtomicU8::new(OVERRIDE_UNSET);
+
+/// Force `should_colorize` to always return `value`, regardless of the environment.
+pub fn set_override(value: bool) {
+    OVERRIDE.store(if value { OVERRIDE_TRUE } else { OVERRIDE_FALSE }, Ordering::SeqCst);
+}
+
+/// Clear a previously set override, restoring the environment-based decision.
+pub fn unset_override() {
+    OVERRIDE.store(OVERRIDE_UNSET, Ordering::SeqCst);
+}
+
+/// Decide whether the `Colored` methods should emit escape sequences.
+///
+/// See the [module docs](self) for the precedence rules.
+pub fn should_colorize() -> bool {
+    match OVERRIDE.load(Ordering::SeqCst) {
+        OVERRIDE_TRUE => return true,
+        OVERRIDE_FALSE => return false,
+        _ => {}
+    }
+
+    if let Ok(value) = std::env::var("CLICOLOR_FORCE") {
+        if value != "0" {
+            return true;
+        }
+    }
+
+    if std::env::var_os("NO_COLOR").is_some() {
+        return false;
+    }
+
+    if let Ok(value) = std::env::var("CLICOLOR") {
+        if value == "0" {
+            return false;
+        }
+    }
+
+    is_stdout_tty()
+}
+
+#[cfg(feature = "atty")]
+fn is_stdout_tty() -> bool {
+    atty::is(atty::Stream::Stdout)
+}
+
+#[cfg(not(feature = "atty"))]
+fn is_stdout_tty() -> bool {
+    true
+}
+
+/// Enable or disable ANSI escape sequence interpretation on the Windows console.
+///
+/// Older Windows 10 consoles require `ENABLE_VIRTUAL_TERMINAL_PROCESSING` to be set on the
+/// stdout handle before they understand the escape sequences this crate emits. This is a no-op
+/// on non-Windows platforms.
+#[cfg(windows)]
+pub fn set_virtual_terminal(enabled: bool) {
+    use windows_sys::Win32::System::Console::{
+        GetConsoleMode, GetStdHandle, SetConsoleMode, ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+        STD_OUTPUT_HANDLE,
+    };
+
+    unsafe {
+        let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+        let mut mode = 0;
+        if GetConsoleMode(handle, &mut mode) == 0 {
+            return;
+        }
+
+        let mode = if enabled {
+            mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING
+        } else {
+            mode & !ENABLE_VIRTUAL_TERMINAL_PROCESSING
+        };
+
+        SetConsoleMode(handle, mode);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn set_virtual_terminal(_enabled: bool) {}
+
+#[cfg(windows)]
+static ENABLE_VT_ONCE: std::sync::Once = std::sync::Once::new();
+
+/// Make sure the Windows console has virtual terminal processing enabled, doing the actual work
+/// only on the first call. A no-op on non-Windows platforms.
+pub(crate) fn ensure_virtual_terminal() {
+    #[cfg(windows)]
+    ENABLE_VT_ONCE.call_once(|| set_virtual_terminal(true));
+}
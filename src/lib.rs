@@ -3,88 +3,277 @@
 //! A simple way to colorize the output of your cli application.
 //!
 //! This crate contains a single trait `Colored` that is implemented for the `String` type.
-//! Calling `.color("red")`, `.background("green")` or `.style("bold")`
-//! will wrap your string with the corresponding ANSI escape sequence.
+//! Calling `.color("red")`, `.background("green")` or `.style("bold")` returns a [`ColoredString`]
+//! that wraps the original text with the corresponding ANSI escape sequence.
 //!
-//! Different styles can be concatenated together:
+//! Different styles can be combined together, and only ever emit a single leading escape sequence
+//! and a single trailing reset:
 //! ```rust
 //! use colorama::Colored;
 //!
-//! let mut s = String::from("colorama");
-//!
-//! s.color("red").background("green").style("bold");
+//! let s = String::from("colorama").color("red").background("green").style("bold");
 //!
 //! println!("{}", s);
-//! assert_eq!(s, "\x1b[1m\x1b[42m\x1b[31mcolorama\x1b[0m\x1b[0m\x1b[0m");
+//! assert_eq!(s.to_string(), "\x1b[1;42;31mcolorama\x1b[0m");
 //! ```
 //! Unknown color / style names are silently ignored.
 //!
-//! Note: This package does not check if the program is running inside a terminal or
-//! if it is called via pipes. If you want this functionality, check out
-//! [termcolor](https://crates.io/crates/termcolor), [colored](https://crates.io/crates/colored)
-//! and / or [atty](https://crates.io/crates/atty).
+//! Colorizing respects the `NO_COLOR`, `CLICOLOR` and `CLICOLOR_FORCE` environment variables, and
+//! falls back to a tty check on stdout (behind the `atty` feature). See the [`control`] module to
+//! override this behavior from code.
+//!
+//! On Windows, the first call to `color`, `background` or `style` automatically enables virtual
+//! terminal processing on the console so the emitted escape sequences render instead of printing
+//! as literal text.
 //!
 
-fn map_color(color: &str) -> Option<&str> {
-    match color {
-        "normal" => Some("\x1b[0m"),
-        "black" => Some("\x1b[30m"),
-        "red" => Some("\x1b[31m"),
-        "green" => Some("\x1b[32m"),
-        "yellow" => Some("\x1b[33m"),
-        "blue" => Some("\x1b[34m"),
-        "magenta" => Some("\x1b[35m"),
-        "cyan" => Some("\x1b[36m"),
-        "white" => Some("\x1b[37m"),
-        "bright black" => Some("\x1b[90m"),
-        "bright red" => Some("\x1b[91m"),
-        "bright green" => Some("\x1b[92m"),
-        "bright yellow" => Some("\x1b[93m"),
-        "bright blue" => Some("\x1b[94m"),
-        "bright magenta" => Some("\x1b[95m"),
-        "bright cyan" => Some("\x1b[96m"),
-        "bright white" => Some("\x1b[97m"),
-        _ => None,
-    }
+pub mod control;
+
+use std::fmt;
+
+/// A terminal color: one of the 16 named ANSI colors, a fixed xterm 256-color palette index, or
+/// a 24-bit truecolor RGB triple.
+///
+/// [`Color::foreground_code`] and [`Color::background_code`] produce the numeric SGR parameter
+/// for each case, so foreground and background callers share one place that knows how colors map
+/// to escape codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Normal,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    Fixed(u8),
+    Rgb(u8, u8, u8),
 }
 
-fn map_background(background: &str) -> Option<&str> {
-    match background {
-        "normal" => Some("\x1b[0m"),
-        "black" => Some("\x1b[40m"),
-        "red" => Some("\x1b[41m"),
-        "green" => Some("\x1b[42m"),
-        "yellow" => Some("\x1b[43m"),
-        "blue" => Some("\x1b[44m"),
-        "magenta" => Some("\x1b[45m"),
-        "cyan" => Some("\x1b[46m"),
-        "white" => Some("\x1b[47m"),
-        "bright black" => Some("\x1b[100m"),
-        "bright red" => Some("\x1b[101m"),
-        "bright green" => Some("\x1b[102m"),
-        "bright yellow" => Some("\x1b[103m"),
-        "bright blue" => Some("\x1b[104m"),
-        "bright magenta" => Some("\x1b[105m"),
-        "bright cyan" => Some("\x1b[106m"),
-        "bright white" => Some("\x1b[107m"),
-        _ => None,
+impl Color {
+    /// Parse one of the named colors accepted by [`Colored::color`] / [`Colored::background`].
+    fn parse(name: &str) -> Option<Color> {
+        Some(match name {
+            "normal" => Color::Normal,
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "white" => Color::White,
+            "bright black" => Color::BrightBlack,
+            "bright red" => Color::BrightRed,
+            "bright green" => Color::BrightGreen,
+            "bright yellow" => Color::BrightYellow,
+            "bright blue" => Color::BrightBlue,
+            "bright magenta" => Color::BrightMagenta,
+            "bright cyan" => Color::BrightCyan,
+            "bright white" => Color::BrightWhite,
+            _ => return None,
+        })
+    }
+
+    /// The numeric SGR parameter used to set this color as the foreground.
+    pub fn foreground_code(self) -> String {
+        match self {
+            Color::Normal => "0".to_string(),
+            Color::Black => "30".to_string(),
+            Color::Red => "31".to_string(),
+            Color::Green => "32".to_string(),
+            Color::Yellow => "33".to_string(),
+            Color::Blue => "34".to_string(),
+            Color::Magenta => "35".to_string(),
+            Color::Cyan => "36".to_string(),
+            Color::White => "37".to_string(),
+            Color::BrightBlack => "90".to_string(),
+            Color::BrightRed => "91".to_string(),
+            Color::BrightGreen => "92".to_string(),
+            Color::BrightYellow => "93".to_string(),
+            Color::BrightBlue => "94".to_string(),
+            Color::BrightMagenta => "95".to_string(),
+            Color::BrightCyan => "96".to_string(),
+            Color::BrightWhite => "97".to_string(),
+            Color::Fixed(n) => format!("38;5;{n}"),
+            Color::Rgb(r, g, b) => format!("38;2;{r};{g};{b}"),
+        }
+    }
+
+    /// The numeric SGR parameter used to set this color as the background.
+    pub fn background_code(self) -> String {
+        match self {
+            Color::Normal => "0".to_string(),
+            Color::Black => "40".to_string(),
+            Color::Red => "41".to_string(),
+            Color::Green => "42".to_string(),
+            Color::Yellow => "43".to_string(),
+            Color::Blue => "44".to_string(),
+            Color::Magenta => "45".to_string(),
+            Color::Cyan => "46".to_string(),
+            Color::White => "47".to_string(),
+            Color::BrightBlack => "100".to_string(),
+            Color::BrightRed => "101".to_string(),
+            Color::BrightGreen => "102".to_string(),
+            Color::BrightYellow => "103".to_string(),
+            Color::BrightBlue => "104".to_string(),
+            Color::BrightMagenta => "105".to_string(),
+            Color::BrightCyan => "106".to_string(),
+            Color::BrightWhite => "107".to_string(),
+            Color::Fixed(n) => format!("48;5;{n}"),
+            Color::Rgb(r, g, b) => format!("48;2;{r};{g};{b}"),
+        }
     }
 }
 
-fn map_style(style: &str) -> Option<&str> {
+fn map_style(style: &str) -> Option<StyleFlags> {
     match style {
-        "normal" => Some("\x1b[0m"),
-        "bold" => Some("\x1b[1m"),
-        "faint" => Some("\x1b[2m"),
-        "italic" => Some("\x1b[3m"),
-        "underline" => Some("\x1b[4m"),
+        "normal" => Some(StyleFlags::empty()),
+        "bold" => Some(StyleFlags::BOLD),
+        "faint" => Some(StyleFlags::FAINT),
+        "italic" => Some(StyleFlags::ITALIC),
+        "underline" => Some(StyleFlags::UNDERLINE),
+        "blink" => Some(StyleFlags::BLINK),
+        "reverse" | "invert" => Some(StyleFlags::REVERSE),
+        "hidden" | "conceal" => Some(StyleFlags::HIDDEN),
+        "strikethrough" => Some(StyleFlags::STRIKETHROUGH),
         _ => None,
     }
 }
 
+/// A set of SGR style attributes, combinable with bitwise OR-like [`StyleFlags::insert`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StyleFlags(u16);
+
+impl StyleFlags {
+    pub const BOLD: StyleFlags = StyleFlags(1 << 0);
+    pub const FAINT: StyleFlags = StyleFlags(1 << 1);
+    pub const ITALIC: StyleFlags = StyleFlags(1 << 2);
+    pub const UNDERLINE: StyleFlags = StyleFlags(1 << 3);
+    pub const BLINK: StyleFlags = StyleFlags(1 << 4);
+    pub const REVERSE: StyleFlags = StyleFlags(1 << 5);
+    pub const HIDDEN: StyleFlags = StyleFlags(1 << 6);
+    pub const STRIKETHROUGH: StyleFlags = StyleFlags(1 << 7);
+
+    pub const fn empty() -> Self {
+        StyleFlags(0)
+    }
+
+    pub fn contains(self, other: StyleFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: StyleFlags) {
+        self.0 |= other.0;
+    }
+
+    fn codes(self) -> Vec<&'static str> {
+        let mut codes = Vec::new();
+        if self.contains(Self::BOLD) {
+            codes.push("1");
+        }
+        if self.contains(Self::FAINT) {
+            codes.push("2");
+        }
+        if self.contains(Self::ITALIC) {
+            codes.push("3");
+        }
+        if self.contains(Self::UNDERLINE) {
+            codes.push("4");
+        }
+        if self.contains(Self::BLINK) {
+            codes.push("5");
+        }
+        if self.contains(Self::REVERSE) {
+            codes.push("7");
+        }
+        if self.contains(Self::HIDDEN) {
+            codes.push("8");
+        }
+        if self.contains(Self::STRIKETHROUGH) {
+            codes.push("9");
+        }
+        codes
+    }
+}
+
+/// A String together with the color and style attributes that should be applied to it.
+///
+/// Attributes are only combined into a single escape sequence when the value is displayed, so
+/// chaining `.color(..)`, `.background(..)` and `.style(..)` never stacks up redundant escape
+/// codes. A `ColoredString` with no attributes set renders as the bare input.
+///
+/// Whether to colorize at all is decided once, from [`control::should_colorize`] at construction
+/// time, and carried along on the value. This way a later call to [`control::set_override`] or
+/// [`control::unset_override`] can't retroactively change how an already-built `ColoredString`
+/// renders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColoredString {
+    input: String,
+    fgcolor: Option<String>,
+    bgcolor: Option<String>,
+    styles: StyleFlags,
+    colorize: bool,
+}
+
+impl ColoredString {
+    fn new(input: String) -> Self {
+        ColoredString {
+            input,
+            fgcolor: None,
+            bgcolor: None,
+            styles: StyleFlags::empty(),
+            colorize: control::should_colorize(),
+        }
+    }
+}
+
+impl fmt::Display for ColoredString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.colorize {
+            return write!(f, "{}", self.input);
+        }
+
+        let mut params = self.styles.codes();
+        if let Some(bg) = &self.bgcolor {
+            params.push(bg);
+        }
+        if let Some(fg) = &self.fgcolor {
+            params.push(fg);
+        }
+
+        if params.is_empty() {
+            write!(f, "{}", self.input)
+        } else {
+            write!(f, "\x1b[{}m{}\x1b[0m", params.join(";"), self.input)
+        }
+    }
+}
+
+impl PartialEq<str> for ColoredString {
+    fn eq(&self, other: &str) -> bool {
+        format!("{self}") == other
+    }
+}
+
+impl PartialEq<&str> for ColoredString {
+    fn eq(&self, other: &&str) -> bool {
+        format!("{self}") == *other
+    }
+}
+
 pub trait Colored {
-    /// Display String in a given color.
-    /// Possible values are:
+    /// Color the String. Possible values are:
     ///
     /// normal, black, red, green, yellow, blue, magenta, cyan, white, bright black, bright red, bright green, bright yellow, bright blue, bright magenta, bright cyan, bright white
     ///
@@ -92,15 +281,13 @@ pub trait Colored {
     /// ```
     /// use colorama::Colored;
     ///
-    /// let mut s = String::from("colorama");
-    /// s.color("red");
+    /// let s = String::from("colorama").color("red");
     ///
     /// assert_eq!(s, "\x1b[31mcolorama\x1b[0m");
     /// ```
-    fn color(&mut self, color: &str) -> &mut Self;
+    fn color(&self, color: &str) -> ColoredString;
 
-    /// Display String with a given background color.
-    /// Possible values are:
+    /// Give the String a background color. Possible values are:
     ///
     /// normal, black, red, green, yellow, blue, magenta, cyan, white, bright black, bright red, bright green, bright yellow, bright blue, bright magenta, bright cyan, bright white
     ///
@@ -108,15 +295,14 @@ pub trait Colored {
     /// ```
     /// use colorama::Colored;
     ///
-    /// let mut s = String::from("colorama");
-    /// s.background("red");
+    /// let s = String::from("colorama").background("red");
     ///
     /// assert_eq!(s, "\x1b[41mcolorama\x1b[0m");
     /// ```
-    fn background(&mut self, background: &str) -> &mut Self;
+    fn background(&self, background: &str) -> ColoredString;
 
-    /// Display String in a given style.
-    /// Possible values are:
+    /// Style the String. Possible values are blink, reverse (or invert), hidden (or conceal),
+    /// strikethrough, and:
     ///
     /// normal, bold, faint, italic, underline
     ///
@@ -124,110 +310,397 @@ pub trait Colored {
     /// ```
     /// use colorama::Colored;
     ///
-    /// let mut s = String::from("colorama");
-    /// s.style("underline");
+    /// let s = String::from("colorama").style("underline");
     ///
     /// assert_eq!(s, "\x1b[4mcolorama\x1b[0m");
     /// ```
-    fn style(&mut self, style: &str) -> &mut Self;
-}
+    fn style(&self, style: &str) -> ColoredString;
 
-impl Colored for String {
-    /// Display String in a given color.
-    /// Possible values are:
-    ///
-    /// normal, black, red, green, yellow, blue, magenta, cyan, white, bright black, bright red, bright green, bright yellow, bright blue, bright magenta, bright cyan, bright white
+    /// Color the String with a color from the xterm 256-color palette.
     ///
     /// # Example
     /// ```
     /// use colorama::Colored;
     ///
-    /// let mut s = String::from("colorama");
-    /// s.color("red");
+    /// let s = String::from("colorama").color_256(208);
     ///
-    /// assert_eq!(s, "\x1b[31mcolorama\x1b[0m");
+    /// assert_eq!(s, "\x1b[38;5;208mcolorama\x1b[0m");
     /// ```
-    fn color(&mut self, color: &str) -> &mut Self {
-        map_color(color).map(|c| {
-            self.insert_str(0, c);
-            self.push_str("\x1b[0m");
-        });
+    fn color_256(&self, n: u8) -> ColoredString;
 
-        self
-    }
+    /// Give the String a background color from the xterm 256-color palette.
+    ///
+    /// # Example
+    /// ```
+    /// use colorama::Colored;
+    ///
+    /// let s = String::from("colorama").background_256(208);
+    ///
+    /// assert_eq!(s, "\x1b[48;5;208mcolorama\x1b[0m");
+    /// ```
+    fn background_256(&self, n: u8) -> ColoredString;
 
-    /// Display String with a given background color.
-    /// Possible values are:
+    /// Color the String with a 24-bit truecolor RGB color.
     ///
-    /// normal, black, red, green, yellow, blue, magenta, cyan, white, bright black, bright red, bright green, bright yellow, bright blue, bright magenta, bright cyan, bright white
+    /// # Example
+    /// ```
+    /// use colorama::Colored;
+    ///
+    /// let s = String::from("colorama").color_rgb(255, 0, 0);
+    ///
+    /// assert_eq!(s, "\x1b[38;2;255;0;0mcolorama\x1b[0m");
+    /// ```
+    fn color_rgb(&self, r: u8, g: u8, b: u8) -> ColoredString;
+
+    /// Give the String a 24-bit truecolor RGB background color.
     ///
     /// # Example
     /// ```
     /// use colorama::Colored;
     ///
-    /// let mut s = String::from("colorama");
-    /// s.background("red");
+    /// let s = String::from("colorama").background_rgb(255, 0, 0);
     ///
-    /// assert_eq!(s, "\x1b[41mcolorama\x1b[0m");
+    /// assert_eq!(s, "\x1b[48;2;255;0;0mcolorama\x1b[0m");
     /// ```
-    fn background(&mut self, background: &str) -> &mut Self {
-        map_background(background).map(|b| {
-            self.insert_str(0, b);
-            self.push_str("\x1b[0m");
-        });
+    fn background_rgb(&self, r: u8, g: u8, b: u8) -> ColoredString;
 
-        self
-    }
+    /// Color the String with a [`Color`], bypassing string parsing.
+    ///
+    /// # Example
+    /// ```
+    /// use colorama::{Color, Colored};
+    ///
+    /// let s = String::from("colorama").color_enum(Color::Red);
+    ///
+    /// assert_eq!(s, "\x1b[31mcolorama\x1b[0m");
+    /// ```
+    fn color_enum(&self, color: Color) -> ColoredString;
 
-    /// Display String in a given style.
-    /// Possible values are:
+    /// Give the String a background [`Color`], bypassing string parsing.
     ///
-    /// normal, bold, faint, italic, underline
+    /// # Example
+    /// ```
+    /// use colorama::{Color, Colored};
+    ///
+    /// let s = String::from("colorama").background_enum(Color::Red);
+    ///
+    /// assert_eq!(s, "\x1b[41mcolorama\x1b[0m");
+    /// ```
+    fn background_enum(&self, color: Color) -> ColoredString;
+
+    /// Strip any color and style attributes applied so far, leaving the plain input.
+    ///
+    /// Named `reset` rather than `clear` so it doesn't shadow `String::clear`'s
+    /// truncate-in-place behavior when this trait is in scope.
     ///
     /// # Example
     /// ```
     /// use colorama::Colored;
     ///
-    /// let mut s = String::from("colorama");
-    /// s.style("underline");
+    /// let s = String::from("colorama").color("red").style("bold").reset();
     ///
-    /// assert_eq!(s, "\x1b[4mcolorama\x1b[0m");
+    /// assert_eq!(s, "colorama");
     /// ```
-    fn style(&mut self, style: &str) -> &mut Self {
-        map_style(style).map(|s| {
-            self.insert_str(0, s);
-            self.push_str("\x1b[0m");
-        });
+    fn reset(&self) -> ColoredString;
+}
+
+impl Colored for String {
+    fn color(&self, color: &str) -> ColoredString {
+        control::ensure_virtual_terminal();
+        let mut s = ColoredString::new(self.clone());
+        s.fgcolor = Color::parse(color).map(Color::foreground_code);
+        s
+    }
+
+    fn background(&self, background: &str) -> ColoredString {
+        control::ensure_virtual_terminal();
+        let mut s = ColoredString::new(self.clone());
+        s.bgcolor = Color::parse(background).map(Color::background_code);
+        s
+    }
+
+    fn style(&self, style: &str) -> ColoredString {
+        control::ensure_virtual_terminal();
+        let mut s = ColoredString::new(self.clone());
+        if let Some(flags) = map_style(style) {
+            s.styles.insert(flags);
+        }
+        s
+    }
+
+    fn color_256(&self, n: u8) -> ColoredString {
+        self.color_enum(Color::Fixed(n))
+    }
 
-        self
+    fn background_256(&self, n: u8) -> ColoredString {
+        self.background_enum(Color::Fixed(n))
+    }
+
+    fn color_rgb(&self, r: u8, g: u8, b: u8) -> ColoredString {
+        self.color_enum(Color::Rgb(r, g, b))
+    }
+
+    fn background_rgb(&self, r: u8, g: u8, b: u8) -> ColoredString {
+        self.background_enum(Color::Rgb(r, g, b))
+    }
+
+    fn color_enum(&self, color: Color) -> ColoredString {
+        control::ensure_virtual_terminal();
+        let mut s = ColoredString::new(self.clone());
+        s.fgcolor = Some(color.foreground_code());
+        s
+    }
+
+    fn background_enum(&self, color: Color) -> ColoredString {
+        control::ensure_virtual_terminal();
+        let mut s = ColoredString::new(self.clone());
+        s.bgcolor = Some(color.background_code());
+        s
+    }
+
+    fn reset(&self) -> ColoredString {
+        ColoredString::new(self.clone())
+    }
+}
+
+impl Colored for ColoredString {
+    fn color(&self, color: &str) -> ColoredString {
+        let mut s = self.clone();
+        if let Some(c) = Color::parse(color) {
+            s.fgcolor = Some(c.foreground_code());
+        }
+        s
+    }
+
+    fn background(&self, background: &str) -> ColoredString {
+        let mut s = self.clone();
+        if let Some(b) = Color::parse(background) {
+            s.bgcolor = Some(b.background_code());
+        }
+        s
+    }
+
+    fn style(&self, style: &str) -> ColoredString {
+        let mut s = self.clone();
+        if let Some(flags) = map_style(style) {
+            s.styles.insert(flags);
+        }
+        s
+    }
+
+    fn color_256(&self, n: u8) -> ColoredString {
+        self.color_enum(Color::Fixed(n))
+    }
+
+    fn background_256(&self, n: u8) -> ColoredString {
+        self.background_enum(Color::Fixed(n))
+    }
+
+    fn color_rgb(&self, r: u8, g: u8, b: u8) -> ColoredString {
+        self.color_enum(Color::Rgb(r, g, b))
+    }
+
+    fn background_rgb(&self, r: u8, g: u8, b: u8) -> ColoredString {
+        self.background_enum(Color::Rgb(r, g, b))
+    }
+
+    fn color_enum(&self, color: Color) -> ColoredString {
+        let mut s = self.clone();
+        s.fgcolor = Some(color.foreground_code());
+        s
+    }
+
+    fn background_enum(&self, color: Color) -> ColoredString {
+        let mut s = self.clone();
+        s.bgcolor = Some(color.background_code());
+        s
+    }
+
+    fn reset(&self) -> ColoredString {
+        ColoredString::new(self.input.clone())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Mutex;
+
+    /// `ColoredString::new` captures [`control::should_colorize`] at construction time, which in
+    /// turn reads the process-global override and environment variables. `override_disables_color`
+    /// and `override_forces_color` mutate both, so every test in this module takes this lock first
+    /// to stop the default parallel test runner from constructing a `ColoredString` elsewhere while
+    /// one of those two tests has the override or environment temporarily in a non-default state.
+    static OVERRIDE_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    /// RAII guard that takes [`OVERRIDE_TEST_LOCK`] and forces [`control::should_colorize`]'s
+    /// decision for its lifetime, restoring it on [`Drop`]. Using `Drop` rather than a trailing
+    /// `control::unset_override()` call means a test that panics mid-assertion still clears the
+    /// override instead of leaving it stuck for every test that runs afterwards; acquiring the
+    /// lock with `unwrap_or_else(|e| e.into_inner())` means that panic doesn't poison the lock
+    /// either, so later tests keep running instead of failing with an unrelated `PoisonError`.
+    struct OverrideGuard<'a> {
+        _lock: std::sync::MutexGuard<'a, ()>,
+    }
+
+    impl OverrideGuard<'_> {
+        fn new(colorize: bool) -> Self {
+            let lock = OVERRIDE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            control::set_override(colorize);
+            Self { _lock: lock }
+        }
+    }
+
+    impl Drop for OverrideGuard<'_> {
+        fn drop(&mut self) {
+            control::unset_override();
+        }
+    }
+
+    /// RAII guard that sets an environment variable for its lifetime and removes it on [`Drop`],
+    /// so a panicking assertion can't leak it into later tests.
+    struct EnvVarGuard(&'static str);
+
+    impl EnvVarGuard {
+        fn set(key: &'static str, value: &str) -> Self {
+            std::env::set_var(key, value);
+            Self(key)
+        }
+    }
+
+    impl Drop for EnvVarGuard {
+        fn drop(&mut self) {
+            std::env::remove_var(self.0);
+        }
+    }
 
     #[test]
     fn no_match() {
-        let mut s = String::from("colorama");
-        s.color("unknown");
+        let _guard = OverrideGuard::new(true);
+        let s = String::from("colorama").color("unknown");
 
         assert_eq!(s, "colorama");
     }
 
     #[test]
     fn color_and_style() {
-        let mut s = String::from("colorama");
-        s.color("red").style("bold");
+        let _guard = OverrideGuard::new(true);
+        let s = String::from("colorama").color("red").style("bold");
 
-        assert_eq!(s, "\x1b[1m\x1b[31mcolorama\x1b[0m\x1b[0m");
+        assert_eq!(s, "\x1b[1;31mcolorama\x1b[0m");
     }
 
     #[test]
     fn color_background_and_style() {
-        let mut s = String::from("colorama");
-        s.color("red").background("green").style("bold");
+        let _guard = OverrideGuard::new(true);
+        let s = String::from("colorama")
+            .color("red")
+            .background("green")
+            .style("bold");
+
+        assert_eq!(s, "\x1b[1;42;31mcolorama\x1b[0m");
+    }
+
+    #[test]
+    fn color_256() {
+        let _guard = OverrideGuard::new(true);
+        let s = String::from("colorama").color_256(208);
+
+        assert_eq!(s, "\x1b[38;5;208mcolorama\x1b[0m");
+    }
+
+    #[test]
+    fn background_256() {
+        let _guard = OverrideGuard::new(true);
+        let s = String::from("colorama").background_256(208);
+
+        assert_eq!(s, "\x1b[48;5;208mcolorama\x1b[0m");
+    }
+
+    #[test]
+    fn color_rgb() {
+        let _guard = OverrideGuard::new(true);
+        let s = String::from("colorama").color_rgb(255, 0, 0);
+
+        assert_eq!(s, "\x1b[38;2;255;0;0mcolorama\x1b[0m");
+    }
+
+    #[test]
+    fn background_rgb() {
+        let _guard = OverrideGuard::new(true);
+        let s = String::from("colorama").background_rgb(255, 0, 0);
+
+        assert_eq!(s, "\x1b[48;2;255;0;0mcolorama\x1b[0m");
+    }
+
+    #[test]
+    fn no_attributes_renders_bare() {
+        let _guard = OverrideGuard::new(true);
+        let s = ColoredString::new(String::from("colorama"));
+
+        assert_eq!(s, "colorama");
+    }
 
-        assert_eq!(s, "\x1b[1m\x1b[42m\x1b[31mcolorama\x1b[0m\x1b[0m\x1b[0m");
+    #[test]
+    fn blink_reverse_hidden_strikethrough() {
+        let _guard = OverrideGuard::new(true);
+        assert_eq!(String::from("colorama").style("blink"), "\x1b[5mcolorama\x1b[0m");
+        assert_eq!(String::from("colorama").style("reverse"), "\x1b[7mcolorama\x1b[0m");
+        assert_eq!(String::from("colorama").style("invert"), "\x1b[7mcolorama\x1b[0m");
+        assert_eq!(String::from("colorama").style("hidden"), "\x1b[8mcolorama\x1b[0m");
+        assert_eq!(String::from("colorama").style("conceal"), "\x1b[8mcolorama\x1b[0m");
+        assert_eq!(
+            String::from("colorama").style("strikethrough"),
+            "\x1b[9mcolorama\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn reset_strips_attributes() {
+        let _guard = OverrideGuard::new(true);
+        let s = String::from("colorama").color("red").style("bold").reset();
+
+        assert_eq!(s, "colorama");
+    }
+
+    #[test]
+    fn override_disables_color() {
+        let _guard = OverrideGuard::new(false);
+        // CLICOLOR_FORCE would force color on by itself; the override must still win.
+        let _env = EnvVarGuard::set("CLICOLOR_FORCE", "1");
+
+        let s = String::from("colorama").color("red");
+        assert_eq!(s, "colorama");
+    }
+
+    #[test]
+    fn override_forces_color() {
+        let _guard = OverrideGuard::new(true);
+        // NO_COLOR would disable color by itself; the override must still win.
+        let _env = EnvVarGuard::set("NO_COLOR", "1");
+
+        let s = String::from("colorama").color("red");
+        assert_eq!(s, "\x1b[31mcolorama\x1b[0m");
+    }
+
+    #[test]
+    fn color_enum_matches_string_parsing() {
+        let _guard = OverrideGuard::new(true);
+        let s = String::from("colorama").color_enum(Color::Red);
+
+        assert_eq!(s, String::from("colorama").color("red"));
+    }
+
+    #[test]
+    fn color_enum_fixed_and_rgb() {
+        let _guard = OverrideGuard::new(true);
+        assert_eq!(
+            String::from("colorama").color_enum(Color::Fixed(208)),
+            "\x1b[38;5;208mcolorama\x1b[0m"
+        );
+        assert_eq!(
+            String::from("colorama").background_enum(Color::Rgb(255, 0, 0)),
+            "\x1b[48;2;255;0;0mcolorama\x1b[0m"
+        );
     }
 }